@@ -3,11 +3,13 @@
 use crate::state::GameState;
 use log::{debug, error, warn};
 use regex::Regex;
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use serde_yml;
+use std::collections::HashSet;
 use std::env;
 use std::fs;
 use std::path::{Path, PathBuf};
+use std::time::Duration;
 
 // --- DATA STRUCTURES (The "Shape" of our Game) ---
 
@@ -21,15 +23,89 @@ pub struct Objective {
     pub conditions: Vec<Condition>,
 }
 
+impl Objective {
+    /// Checks every condition for this objective against `user_command`.
+    /// This is the Library/Mission system's equivalent of
+    /// `quest::Quest::check`, reached from `supershell verify`.
+    pub fn is_met(&self, user_command: &str, game: &GameState) -> bool {
+        self.conditions
+            .iter()
+            .all(|c| matches!(c.check(user_command, game), ValidationResult::Valid))
+    }
+}
+
 /// A wrapper around our ConditionType enum.
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Clone)]
 pub struct Condition {
-    #[serde(flatten)]
-    pub condition_type: ConditionType,
-    #[serde(default)]
+    pub condition_type: ConditionKind,
     pub negate: bool,
 }
 
+/// Either one of our built-in checks, or a tag we don't recognize that we
+/// hand off to whatever [`ConditionBackend`] registered itself for it.
+#[derive(Debug, Clone)]
+pub enum ConditionKind {
+    Builtin(ConditionType),
+    Custom { tag: String, params: serde_yml::Value },
+}
+
+impl<'de> Deserialize<'de> for Condition {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let value = serde_yml::Value::deserialize(deserializer)?;
+
+        let negate = value
+            .get("negate")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false);
+
+        // Try the closed set of built-ins first; anything that doesn't
+        // match one of those tags falls through to the backend registry
+        // instead of a hard deserialize error.
+        let condition_type = match serde_yml::from_value::<ConditionType>(value.clone()) {
+            Ok(builtin) => ConditionKind::Builtin(builtin),
+            Err(_) => {
+                let tag = value
+                    .get("type")
+                    .and_then(|v| v.as_str())
+                    .unwrap_or("")
+                    .to_string();
+                ConditionKind::Custom { tag, params: value }
+            }
+        };
+
+        Ok(Condition {
+            condition_type,
+            negate,
+        })
+    }
+}
+
+impl Serialize for Condition {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        // Re-flatten `negate` back onto whatever the condition type
+        // serializes to, mirroring how it was read in.
+        let mut value = match &self.condition_type {
+            ConditionKind::Builtin(ct) => {
+                serde_yml::to_value(ct).map_err(serde::ser::Error::custom)?
+            }
+            ConditionKind::Custom { params, .. } => params.clone(),
+        };
+        if let serde_yml::Value::Mapping(ref mut map) = value {
+            map.insert(
+                serde_yml::Value::String("negate".into()),
+                serde_yml::Value::Bool(self.negate),
+            );
+        }
+        value.serialize(serializer)
+    }
+}
+
 /// The specific types of checks we can perform.
 /// Tagged with "type" so the YAML knows which one is which.
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -39,8 +115,22 @@ pub enum ConditionType {
     WorkingDir { path: String },
     FileExists { path: String },
     FileContentMatches { path: String, pattern: String },
+    /// Runs `command` through the system shell and checks what it actually
+    /// produced, rather than pattern-matching the text the player typed.
+    CommandOutput {
+        command: String,
+        #[serde(default)]
+        stdout_pattern: Option<String>,
+        #[serde(default)]
+        exit_code: Option<i32>,
+        #[serde(default)]
+        timeout_ms: Option<u64>,
+    },
 }
 
+/// How long a `CommandOutput` check waits before giving up on a hung command.
+const DEFAULT_COMMAND_TIMEOUT_MS: u64 = 5_000;
+
 /// A simple enum to communicate success/failure back to main.rs
 pub enum ValidationResult {
     Valid,
@@ -48,43 +138,26 @@ pub enum ValidationResult {
 }
 
 impl Condition {
-    pub fn check(&self, user_cmd: &str, _game: &GameState) -> ValidationResult {
+    pub fn check(&self, user_cmd: &str, game: &GameState) -> ValidationResult {
         // 1. Core Logic
         let result = match &self.condition_type {
-            ConditionType::CommandMatches { pattern } => {
-                let re = Regex::new(pattern).unwrap();
-                let matched = re.is_match(user_cmd);
-                debug!(
-                    "Condition [CommandMatches]: Input='{}' Pattern='{}' Match={}",
-                    user_cmd, pattern, matched
-                );
-                matched
-            }
-            ConditionType::WorkingDir { path } => {
-                let current = env::current_dir()
-                    .unwrap_or_default()
-                    .to_string_lossy()
-                    .to_string();
-                let re = Regex::new(path).unwrap();
-                let matched = re.is_match(&current);
-                debug!(
-                    "Condition [WorkingDir]: PWD='{}' Target='{}' Match={}",
-                    current, path, matched
-                );
-                matched
-            }
-            ConditionType::FileExists { path } => {
-                let exists = Path::new(path).exists();
-                debug!("Condition [FileExists]: Path='{}' Exists={}", path, exists);
-                exists
-            }
-            ConditionType::FileContentMatches { path, pattern } => {
-                if let Ok(content) = fs::read_to_string(path) {
-                    let re = Regex::new(pattern).unwrap_or_else(|_| Regex::new(".*").unwrap());
-                    // NOTE: Add debug!() call here later
-                    re.is_match(&content)
-                } else {
-                    false
+            ConditionKind::Builtin(builtin) => check_builtin(builtin, user_cmd),
+            ConditionKind::Custom { tag, params } => {
+                match backend::registry().check(tag, params, user_cmd, game) {
+                    Some(ValidationResult::Valid) => true,
+                    Some(ValidationResult::Invalid(_)) => false,
+                    None => {
+                        warn!(
+                            "No condition backend registered for type '{}'; treating as failed",
+                            tag
+                        );
+                        // Fail regardless of `negate`: an unknown check
+                        // must never pass just because it was negated.
+                        return ValidationResult::Invalid(format!(
+                            "Unknown condition type '{}'",
+                            tag
+                        ));
+                    }
                 }
             }
         };
@@ -100,6 +173,235 @@ impl Condition {
     }
 }
 
+/// The built-in checks, handled natively rather than through the registry
+/// so the common case stays a plain match with no lock/lookup overhead.
+fn check_builtin(condition_type: &ConditionType, user_cmd: &str) -> bool {
+    match condition_type {
+        ConditionType::CommandMatches { pattern } => {
+            let re = Regex::new(pattern).unwrap();
+            let matched = re.is_match(user_cmd);
+            debug!(
+                "Condition [CommandMatches]: Input='{}' Pattern='{}' Match={}",
+                user_cmd, pattern, matched
+            );
+            matched
+        }
+        ConditionType::WorkingDir { path } => {
+            let current = env::current_dir()
+                .unwrap_or_default()
+                .to_string_lossy()
+                .to_string();
+            let re = Regex::new(path).unwrap();
+            let matched = re.is_match(&current);
+            debug!(
+                "Condition [WorkingDir]: PWD='{}' Target='{}' Match={}",
+                current, path, matched
+            );
+            matched
+        }
+        ConditionType::FileExists { path } => {
+            let exists = Path::new(path).exists();
+            debug!("Condition [FileExists]: Path='{}' Exists={}", path, exists);
+            exists
+        }
+        ConditionType::FileContentMatches { path, pattern } => {
+            if let Ok(content) = fs::read_to_string(path) {
+                let re = Regex::new(pattern).unwrap_or_else(|_| Regex::new(".*").unwrap());
+                // NOTE: Add debug!() call here later
+                re.is_match(&content)
+            } else {
+                false
+            }
+        }
+        ConditionType::CommandOutput {
+            command,
+            stdout_pattern,
+            exit_code,
+            timeout_ms,
+        } => {
+            let timeout = Duration::from_millis(timeout_ms.unwrap_or(DEFAULT_COMMAND_TIMEOUT_MS));
+            match run_with_timeout(command, timeout) {
+                Some(output) => {
+                    let stdout_ok = match stdout_pattern {
+                        Some(pattern) => match Regex::new(pattern) {
+                            Ok(re) => re.is_match(&output.stdout),
+                            Err(e) => {
+                                error!("Invalid stdout_pattern '{}': {}", pattern, e);
+                                false
+                            }
+                        },
+                        None => true,
+                    };
+                    let exit_ok = match exit_code {
+                        Some(expected) => output.exit_code == Some(*expected),
+                        None => true,
+                    };
+                    debug!(
+                        "Condition [CommandOutput]: cmd='{}' exit={:?} stdout_ok={} exit_ok={}",
+                        command, output.exit_code, stdout_ok, exit_ok
+                    );
+                    stdout_ok && exit_ok
+                }
+                None => {
+                    warn!(
+                        "Condition [CommandOutput]: '{}' timed out after {:?}",
+                        command, timeout
+                    );
+                    false
+                }
+            }
+        }
+    }
+}
+
+/// Captured result of a `CommandOutput` check.
+struct CommandRunOutput {
+    stdout: String,
+    exit_code: Option<i32>,
+}
+
+/// Runs `command` through [`crate::exec::run`], the same "spawn via shell,
+/// wait on a helper thread, kill on timeout" helper `exec::run` (the
+/// player's real-command validation) already uses, rather than keeping a
+/// second, drifting copy of that logic here.
+fn run_with_timeout(command: &str, timeout: Duration) -> Option<CommandRunOutput> {
+    crate::exec::run(command, timeout).map(|output| CommandRunOutput {
+        stdout: output.stdout,
+        exit_code: output.exit_code,
+    })
+}
+
+// --- PLUGGABLE CONDITION BACKENDS ---
+//
+// `ConditionType` above is a closed set we control, so every new check used
+// to mean editing this crate. `ConditionBackend` lets a quest pack register
+// its own `type:` tags (e.g. `EnvVarSet`, `ProcessRunning`) at startup and
+// have `Condition::check` dispatch to them by name, the same way an
+// external DVCS lets third parties add backends without a core release.
+pub mod backend {
+    use super::{GameState, ValidationResult};
+    use std::sync::{Mutex, OnceLock};
+
+    pub trait ConditionBackend: Send + Sync {
+        fn tag(&self) -> &str;
+        fn check(&self, params: &serde_yml::Value, user_cmd: &str, game: &GameState) -> ValidationResult;
+    }
+
+    #[derive(Default)]
+    pub struct Registry {
+        backends: Mutex<Vec<Box<dyn ConditionBackend>>>,
+    }
+
+    impl Registry {
+        pub fn register(&self, backend: Box<dyn ConditionBackend>) {
+            self.backends.lock().unwrap().push(backend);
+        }
+
+        /// Looks up a backend by the YAML `type` string and runs it.
+        /// Returns `None` if nothing is registered for that tag, which the
+        /// caller treats as the condition failing rather than a crash.
+        pub fn check(
+            &self,
+            tag: &str,
+            params: &serde_yml::Value,
+            user_cmd: &str,
+            game: &GameState,
+        ) -> Option<ValidationResult> {
+            let backends = self.backends.lock().unwrap();
+            backends
+                .iter()
+                .find(|b| b.tag() == tag)
+                .map(|b| b.check(params, user_cmd, game))
+        }
+    }
+
+    static REGISTRY: OnceLock<Registry> = OnceLock::new();
+
+    /// The process-wide registry. Built-ins are registered into it lazily
+    /// on first access so a fresh process always has them available.
+    pub fn registry() -> &'static Registry {
+        REGISTRY.get_or_init(|| {
+            let registry = Registry::default();
+            super::register_builtin_backends(&registry);
+            registry
+        })
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        struct AlwaysValid;
+        impl ConditionBackend for AlwaysValid {
+            fn tag(&self) -> &str {
+                "AlwaysValid"
+            }
+            fn check(&self, _params: &serde_yml::Value, _user_cmd: &str, _game: &GameState) -> ValidationResult {
+                ValidationResult::Valid
+            }
+        }
+
+        #[test]
+        fn registry_dispatches_to_the_matching_backend() {
+            let registry = Registry::default();
+            registry.register(Box::new(AlwaysValid));
+
+            let result = registry.check("AlwaysValid", &serde_yml::Value::Null, "anything", &GameState::new());
+
+            assert!(matches!(result, Some(ValidationResult::Valid)));
+        }
+
+        #[test]
+        fn registry_returns_none_for_an_unregistered_tag() {
+            let registry = Registry::default();
+
+            let result = registry.check("NoSuchTag", &serde_yml::Value::Null, "anything", &GameState::new());
+
+            assert!(result.is_none());
+        }
+    }
+}
+
+/// Wraps each built-in `ConditionType` variant as a [`backend::ConditionBackend`]
+/// and registers it under its YAML tag, so unrecognized-but-matching tags
+/// still resolve through the same registry path as third-party backends.
+fn register_builtin_backends(registry: &backend::Registry) {
+    macro_rules! builtin_backend {
+        ($name:ident, $tag:literal) => {
+            struct $name;
+            impl backend::ConditionBackend for $name {
+                fn tag(&self) -> &str {
+                    $tag
+                }
+                fn check(
+                    &self,
+                    params: &serde_yml::Value,
+                    user_cmd: &str,
+                    _game: &GameState,
+                ) -> ValidationResult {
+                    match serde_yml::from_value::<ConditionType>(params.clone()) {
+                        Ok(condition_type) => {
+                            if check_builtin(&condition_type, user_cmd) {
+                                ValidationResult::Valid
+                            } else {
+                                ValidationResult::Invalid("Condition not met".into())
+                            }
+                        }
+                        Err(e) => ValidationResult::Invalid(e.to_string()),
+                    }
+                }
+            }
+            registry.register(Box::new($name));
+        };
+    }
+
+    builtin_backend!(CommandMatchesBackend, "CommandMatches");
+    builtin_backend!(WorkingDirBackend, "WorkingDir");
+    builtin_backend!(FileExistsBackend, "FileExists");
+    builtin_backend!(FileContentMatchesBackend, "FileContentMatches");
+    builtin_backend!(CommandOutputBackend, "CommandOutput");
+}
+
 // --- LIBRARY SYSTEM ---
 
 pub struct Library {
@@ -111,53 +413,124 @@ impl Library {
         Self { root_dir }
     }
 
-    /// Scans the library folder for .yaml files (Quests)
+    /// Recursively scans the library folder (and any subfolders, e.g.
+    /// `beginner/`, `networking/`, `ctf/`) for .yaml/.yml files.
     pub fn list_modules(&self) -> Vec<(PathBuf, String)> {
         let mut modules = Vec::new();
-        if let Ok(entries) = fs::read_dir(&self.root_dir) {
-            for entry in entries.flatten() {
-                let path = entry.path();
-                if let Some(ext) = path.extension() {
-                    if ext == "yaml" || ext == "yml" {
-                        match Module::load(&path) {
-                            Ok(module) => {
-                                let display_name = if module.title.is_empty() {
-                                    path.file_stem().unwrap().to_string_lossy().to_string()
-                                } else {
-                                    module.title
-                                };
-                                modules.push((path, display_name));
-                            }
-                            Err(e) => {
-                                // Important: Log corrupt files so we know to fix them
-                                warn!("Failed to load module {:?}: {}", path, e);
-                            }
-                        }
-                    }
-                }
-            }
-        }
+        let mut seen = HashSet::new();
+        Self::walk(&self.root_dir, &self.root_dir, &mut modules, &mut seen);
+
         // Alphabetize the list
         modules.sort_by(|a, b| a.1.cmp(&b.1));
         modules
     }
 
-    /// Finds a specific course by name
+    /// Depth-first descent into `dir`, pushing every module found under it.
+    fn walk(
+        root_dir: &Path,
+        dir: &Path,
+        modules: &mut Vec<(PathBuf, String)>,
+        seen: &mut HashSet<String>,
+    ) {
+        let Ok(entries) = fs::read_dir(dir) else {
+            return;
+        };
+
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.is_dir() {
+                Self::walk(root_dir, &path, modules, seen);
+                continue;
+            }
+
+            let Some(ext) = path.extension() else {
+                continue;
+            };
+            if ext != "yaml" && ext != "yml" {
+                continue;
+            }
+
+            let relative_key = relative_key(root_dir, &path);
+            if !seen.insert(relative_key.clone()) {
+                continue;
+            }
+
+            match Module::load(&path) {
+                Ok(module) => {
+                    modules.push((path.clone(), display_name(root_dir, &path, &module)));
+                }
+                Err(e) => {
+                    // Important: Log corrupt files so we know to fix them
+                    warn!("Failed to load module {:?}: {}", path, e);
+                }
+            }
+        }
+    }
+
+    /// Finds a specific course by its name or category-prefixed relative
+    /// path (e.g. `dns` or `networking/dns`).
     pub fn get_module(&self, module_name: &str) -> Option<Module> {
-        // 1. Try finding it directly by filename
-        let path = self.root_dir.join(format!("{}.yaml", module_name));
-        if path.exists() {
-            return Module::load(&path).ok();
+        // 1. Try finding it directly by filename at the library root.
+        for candidate in [
+            self.root_dir.join(format!("{}.yaml", module_name)),
+            self.root_dir.join(format!("{}.yml", module_name)),
+        ] {
+            if candidate.exists() {
+                return Module::load(&candidate).ok();
+            }
         }
 
-        // 2. Fallback: Search inside files (slower but safer)
+        // 2. Fallback: resolve against the full recursive set by relative
+        // path, so nested modules are addressable as "category/name".
         self.list_modules()
             .into_iter()
-            .find(|(p, _)| p.file_stem().unwrap().to_string_lossy() == module_name)
+            .find(|(p, _)| relative_key(&self.root_dir, p) == module_name)
             .and_then(|(p, _)| Module::load(&p).ok())
     }
 }
 
+/// The module's path relative to the library root, without its extension,
+/// joined with forward slashes regardless of platform so a lookup key like
+/// "networking/dns" is stable across Windows and Unix.
+fn relative_key(root_dir: &Path, path: &Path) -> String {
+    let relative = path.strip_prefix(root_dir).unwrap_or(path).with_extension("");
+    relative
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy().into_owned())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// The name shown in module listings: the module's own title (or its file
+/// stem, if untitled) prefixed with its subfolder as a category, so
+/// "networking/dns.yaml" with title "DNS Basics" shows as "networking/DNS
+/// Basics" instead of colliding with a same-named module elsewhere.
+fn display_name(root_dir: &Path, path: &Path, module: &Module) -> String {
+    let title = if module.title.is_empty() {
+        path.file_stem().unwrap().to_string_lossy().to_string()
+    } else {
+        module.title.clone()
+    };
+
+    let category = path
+        .strip_prefix(root_dir)
+        .ok()
+        .and_then(|rel| rel.parent())
+        .filter(|parent| !parent.as_os_str().is_empty())
+        .map(|parent| {
+            parent
+                .components()
+                .map(|c| c.as_os_str().to_string_lossy().into_owned())
+                .collect::<Vec<_>>()
+                .join("/")
+        });
+
+    match category {
+        Some(category) => format!("{}/{}", category, title),
+        None => title,
+    }
+}
+
 /// Represents an entire Level (Course)
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Module {
@@ -196,3 +569,61 @@ pub struct Mission {
     #[serde(default)]
     pub setup_actions: Vec<crate::actions::SetupAction>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn list_modules_dedups_same_relative_key_across_extensions() {
+        let root = tempfile::tempdir().unwrap();
+        fs::write(root.path().join("dns.yaml"), "title: DNS\nmissions: []\n").unwrap();
+        fs::write(root.path().join("dns.yml"), "title: DNS (yml)\nmissions: []\n").unwrap();
+
+        let library = Library::new(root.path().to_path_buf());
+        let modules = library.list_modules();
+
+        assert_eq!(modules.len(), 1, "{:?}", modules);
+    }
+
+    #[test]
+    fn list_modules_recurses_into_subfolders() {
+        let root = tempfile::tempdir().unwrap();
+        fs::create_dir_all(root.path().join("networking")).unwrap();
+        fs::write(
+            root.path().join("networking").join("dns.yaml"),
+            "title: DNS\nmissions: []\n",
+        )
+        .unwrap();
+
+        let library = Library::new(root.path().to_path_buf());
+        let modules = library.list_modules();
+
+        assert_eq!(modules.len(), 1);
+        assert_eq!(modules[0].1, "networking/DNS");
+    }
+
+    #[test]
+    fn get_module_resolves_a_nested_category_path() {
+        let root = tempfile::tempdir().unwrap();
+        fs::create_dir_all(root.path().join("networking")).unwrap();
+        fs::write(
+            root.path().join("networking").join("dns.yaml"),
+            "title: DNS\nmissions: []\n",
+        )
+        .unwrap();
+
+        let library = Library::new(root.path().to_path_buf());
+        let module = library.get_module("networking/dns").unwrap();
+
+        assert_eq!(module.title, "DNS");
+    }
+
+    #[test]
+    fn get_module_returns_none_for_an_unknown_name() {
+        let root = tempfile::tempdir().unwrap();
+        let library = Library::new(root.path().to_path_buf());
+
+        assert!(library.get_module("nope").is_none());
+    }
+}
@@ -0,0 +1,72 @@
+use std::process::{Command, Stdio};
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+
+/// Default timeout for executing a player's command. Long enough for most
+/// CLI tools, short enough that a hung interactive program (`vim` with no
+/// file, `ssh` with no host) doesn't block the game forever.
+pub const DEFAULT_TIMEOUT_MS: u64 = 5_000;
+
+/// Captured result of running a player's command for real.
+#[derive(Debug, Clone)]
+pub struct ExecResult {
+    pub stdout: String,
+    pub stderr: String,
+    pub exit_code: Option<i32>,
+}
+
+/// Spawns `command_line` through the system shell, captures stdout/stderr,
+/// and enforces `timeout`. Returns `None` if the command couldn't be
+/// spawned, its output couldn't be read, or the deadline elapsed (in which
+/// case the still-running child is killed).
+pub fn run(command_line: &str, timeout: Duration) -> Option<ExecResult> {
+    #[cfg(windows)]
+    let mut cmd = {
+        let mut c = Command::new("cmd");
+        c.arg("/C").arg(command_line);
+        c
+    };
+    #[cfg(not(windows))]
+    let mut cmd = {
+        let mut c = Command::new("/bin/sh");
+        c.arg("-c").arg(command_line);
+        c
+    };
+
+    let mut child = cmd.stdout(Stdio::piped()).stderr(Stdio::piped()).spawn().ok()?;
+    let pid = child.id();
+
+    // `Child` isn't `Clone`, so the deadline is enforced by how long we
+    // block on a channel, not by anything the waiting thread does itself.
+    let (tx, rx) = mpsc::channel();
+    thread::spawn(move || {
+        let result = child.wait_with_output();
+        let _ = tx.send(result);
+    });
+
+    match rx.recv_timeout(timeout) {
+        Ok(Ok(output)) => Some(ExecResult {
+            stdout: String::from_utf8_lossy(&output.stdout).into_owned(),
+            stderr: String::from_utf8_lossy(&output.stderr).into_owned(),
+            exit_code: output.status.code(),
+        }),
+        Ok(Err(_)) => None,
+        Err(_) => {
+            kill(pid);
+            None
+        }
+    }
+}
+
+#[cfg(unix)]
+fn kill(pid: u32) {
+    let _ = Command::new("kill").arg("-9").arg(pid.to_string()).status();
+}
+
+#[cfg(windows)]
+fn kill(pid: u32) {
+    let _ = Command::new("taskkill")
+        .args(["/PID", &pid.to_string(), "/F"])
+        .status();
+}
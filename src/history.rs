@@ -0,0 +1,95 @@
+use log::warn;
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// One submitted command, recorded alongside what quest it was checked
+/// against and whether it passed. Stored as JSON-lines so new attempts can
+/// just be appended, the same way `GameState::save` treats `save.json` as
+/// the single source of truth for progress.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct HistoryEntry {
+    pub timestamp: u64,
+    pub quest_id: String,
+    pub command: String,
+    pub passed: bool,
+}
+
+/// Append-only log of command attempts, stored next to `save.json` in the
+/// XDG data dir.
+pub struct History {
+    path: PathBuf,
+}
+
+impl History {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    /// Records one attempt. Failures to write are logged, not fatal --
+    /// losing a history line shouldn't block the player from continuing.
+    pub fn record(&self, quest_id: &str, command: &str, passed: bool) {
+        let entry = HistoryEntry {
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or(0),
+            quest_id: quest_id.to_string(),
+            command: command.to_string(),
+            passed,
+        };
+
+        let line = match serde_json::to_string(&entry) {
+            Ok(line) => line,
+            Err(e) => {
+                warn!("Failed to serialize history entry: {}", e);
+                return;
+            }
+        };
+        let mut file = match OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+        {
+            Ok(file) => file,
+            Err(e) => {
+                warn!("Failed to open history file {:?}: {}", self.path, e);
+                return;
+            }
+        };
+        if let Err(e) = writeln!(file, "{}", line) {
+            warn!("Failed to write history entry: {}", e);
+        }
+    }
+
+    /// Loads every recorded attempt, oldest first. Corrupt or truncated
+    /// lines are skipped rather than failing the whole read.
+    pub fn load_all(&self) -> Vec<HistoryEntry> {
+        let Ok(content) = fs::read_to_string(&self.path) else {
+            return Vec::new();
+        };
+        content
+            .lines()
+            .filter_map(|line| serde_json::from_str(line).ok())
+            .collect()
+    }
+}
+
+/// Reads and pretty-prints the full history log, for `supershell history`.
+pub fn print_history(path: &Path) {
+    let entries = History::new(path.to_path_buf()).load_all();
+    if entries.is_empty() {
+        println!("No command history yet.");
+        return;
+    }
+
+    for entry in entries {
+        let status = if entry.passed { "PASS" } else { "FAIL" };
+        println!(
+            "[{}] {:<4} {:<20} {}",
+            entry.timestamp, status, entry.quest_id, entry.command
+        );
+    }
+}
@@ -1,23 +1,65 @@
+mod actions;
+mod content;
+mod exec;
+mod history;
 mod quest;
+mod shell;
 mod state;
 mod ui;
+mod world;
 
-use clap::Parser;
+use clap::{Parser, Subcommand};
+use content::Library;
 use directories::ProjectDirs;
-use quest::{Condition, load_quests};
+use history::History;
+use quest::load_quests;
 use state::GameState;
 use std::fs;
 use std::path::PathBuf;
-use ui::play_cutscene;
+use ui::{install_signal_handler, play_cutscene, play_quest_scene, run_play_loop};
+use world::WorldEngine;
 
 #[derive(Parser)]
 struct Cli {
+    #[command(subcommand)]
+    command: Option<Commands>,
+
     /// The user command to validate
     #[arg(long)]
     check: Option<String>,
 }
 
+#[derive(Subcommand)]
+enum Commands {
+    /// Enter an interactive REPL that tracks protocol progress in-process
+    Play,
+    /// Print the log of every command attempt, pass or fail
+    History,
+    /// Spawn the user's real shell with the puzzle command hooks installed
+    Shell,
+    /// List every quest module discoverable in the module library
+    Modules,
+    /// Build a module's world (setup_actions) under ~/Construct
+    Build {
+        /// Module name, or "category/name" for a nested module
+        module: String,
+    },
+    /// Check a command against one objective's conditions in a module,
+    /// the Library/Mission system's equivalent of `--check`
+    Verify {
+        /// Module name, or "category/name" for a nested module
+        module: String,
+        /// Which mission in the module (0-based)
+        mission: usize,
+        /// Which objective within that mission (0-based)
+        objective: usize,
+        /// The command to check
+        command: String,
+    },
+}
+
 fn main() {
+    install_signal_handler();
     let args = Cli::parse();
 
     // 1. DISCOVER STANDARD PATHS (XDG)
@@ -36,6 +78,18 @@ fn main() {
     // 3. DEFINE FILE PATHS
     // Save file ALWAYS goes to the system data folder
     let save_path = data_dir.join("save.json");
+    let history_path = data_dir.join("history.jsonl");
+
+    // Module library strategy: same System -> Local (Dev) fallback as the
+    // quest file below, but a missing library is not fatal -- it just means
+    // `modules`/`build` have nothing to list yet.
+    let system_library_dir = data_dir.join("library");
+    let local_library_dir = PathBuf::from("library");
+    let library_dir = if system_library_dir.exists() {
+        system_library_dir
+    } else {
+        local_library_dir
+    };
 
     // Quest file strategy: Check System -> Fallback to Local (Dev)
     let system_quest_path = data_dir.join("quests.yaml");
@@ -61,20 +115,104 @@ fn main() {
     // 4. LOAD ENGINE
     let mut game = GameState::load(save_file);
     let quest_db = load_quests(quest_file);
+    let history = History::new(history_path.clone());
 
     // --- LOGIC LOOP (Same as before) ---
+    if let Some(Commands::History) = args.command {
+        history::print_history(&history_path);
+        return;
+    }
+
+    if let Some(Commands::Play) = args.command {
+        run_play_loop(&quest_db, game, save_file, &history);
+        return;
+    }
+
+    if let Some(Commands::Shell) = args.command {
+        shell::launch_infected_session();
+        return;
+    }
+
+    let library = Library::new(library_dir);
+
+    if let Some(Commands::Modules) = args.command {
+        let modules = library.list_modules();
+        if modules.is_empty() {
+            println!("No modules found in the library.");
+        } else {
+            for (path, name) in modules {
+                println!("{}  ({})", name, path.display());
+            }
+        }
+        return;
+    }
+
+    if let Some(Commands::Build { module }) = &args.command {
+        match library.get_module(module) {
+            Some(m) => {
+                let engine = WorldEngine::new();
+                engine.initialize();
+                let report = engine.build_scenario(&m.setup_actions, &game);
+                if report.all_succeeded() {
+                    println!(">> [SYSTEM] Module '{}' world built.", module);
+                } else {
+                    for status in report.failures() {
+                        eprintln!("  ! {:?}: {:?}", status.action, status.outcome);
+                    }
+                    std::process::exit(1);
+                }
+            }
+            None => {
+                eprintln!("Module '{}' not found.", module);
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
+
+    if let Some(Commands::Verify {
+        module,
+        mission,
+        objective,
+        command,
+    }) = &args.command
+    {
+        match library.get_module(module) {
+            Some(m) => match m.missions.get(*mission).and_then(|mi| mi.objectives.get(*objective)) {
+                Some(obj) => {
+                    if obj.is_met(command, &game) {
+                        println!(">> {}", obj.success_msg);
+                    } else {
+                        println!("[!] Criteria not met.");
+                        std::process::exit(1);
+                    }
+                }
+                None => {
+                    eprintln!("No mission {} objective {} in module '{}'.", mission, objective, module);
+                    std::process::exit(1);
+                }
+            },
+            None => {
+                eprintln!("Module '{}' not found.", module);
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
+
     if let Some(user_cmd) = args.check {
         let current_quest = quest_db.get(&game.current_quest_id);
 
         if let Some(quest) = current_quest {
-            let all_met = quest
-                .conditions
-                .iter()
-                .all(|c: &Condition| c.is_met(&user_cmd));
+            // The shell hook (`_g`) already ran this command once itself and
+            // hands us a lossily-reconstructed string, so unlike the REPL we
+            // must not let `check` re-execute it -- see `check_without_exec`.
+            let all_met = quest.check_without_exec(&user_cmd);
+            history.record(&game.current_quest_id, &user_cmd, all_met);
 
             if all_met {
-                play_cutscene(&quest.message);
-                game.complete_current_quest(&quest.next_quest_id);
+                let next_quest_id = play_quest_scene(quest);
+                game.complete_current_quest_with_choice(&quest.id, &next_quest_id);
                 game.save(save_file);
             }
         }
@@ -1,8 +1,11 @@
+use crate::exec::{self, ExecResult};
+use log::warn;
 use regex::Regex;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fs;
 use std::path::Path;
+use std::time::Duration;
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(tag = "type")]
@@ -11,10 +14,29 @@ pub enum Condition {
     FileExists { path: String },
     FileContains { path: String, pattern: String },
     FileMissing { path: String },
+    /// Checks the player's command's real stdout rather than the text they
+    /// typed, so e.g. `ls` only passes once it actually lists a file.
+    StdoutContains { text: String },
+    StdoutMatches { pattern: String },
+    ExitCode { code: i32 },
+    StderrEmpty,
 }
 
 impl Condition {
-    pub fn is_met(&self, user_command: &str) -> bool {
+    /// Whether this condition needs the player's command to actually be
+    /// run. Quests only pay the cost (and the side effects) of executing
+    /// the command once, shared across every condition that needs it.
+    fn needs_exec(&self) -> bool {
+        matches!(
+            self,
+            Condition::StdoutContains { .. }
+                | Condition::StdoutMatches { .. }
+                | Condition::ExitCode { .. }
+                | Condition::StderrEmpty
+        )
+    }
+
+    pub fn is_met(&self, user_command: &str, exec_result: Option<&ExecResult>) -> bool {
         match self {
             Condition::CommandMatches { pattern } => {
                 let re = Regex::new(pattern).unwrap_or_else(|_| Regex::new("").unwrap());
@@ -30,10 +52,51 @@ impl Condition {
                 }
             }
             Condition::FileMissing { path } => !Path::new(path).exists(),
+            Condition::StdoutContains { text } => {
+                exec_result.is_some_and(|r| r.stdout.contains(text.as_str()))
+            }
+            Condition::StdoutMatches { pattern } => exec_result.is_some_and(|r| {
+                Regex::new(pattern)
+                    .map(|re| re.is_match(&r.stdout))
+                    .unwrap_or(false)
+            }),
+            Condition::ExitCode { code } => {
+                exec_result.is_some_and(|r| r.exit_code == Some(*code))
+            }
+            Condition::StderrEmpty => exec_result.is_some_and(|r| r.stderr.trim().is_empty()),
         }
     }
 }
 
+/// One option in a `Choice` beat. Picking it sends the player to
+/// `next_quest_id` instead of the quest's own, overriding it.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ChoiceOption {
+    pub label: String,
+    pub next_quest_id: String,
+}
+
+/// A single step of a quest's cutscene. Quests that only need the old
+/// linear "type out `message`, wait for Space" flow can leave `scene`
+/// empty; richer ones compose these into dialogue with branching.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(tag = "type")]
+pub enum CutsceneBeat {
+    /// Types out `body` with the usual typewriter effect, then waits for
+    /// Space before moving on to the next beat.
+    Text { body: String },
+    /// A timed, non-interactive beat (e.g. a dramatic silence). Skippable
+    /// with Space just like the typewriter effect.
+    Pause { ms: u64 },
+    /// Renders `options` as centered boxes and lets the player pick one
+    /// with the arrow keys and Enter. The chosen option's `next_quest_id`
+    /// overrides the quest's own once the scene finishes.
+    Choice {
+        prompt: String,
+        options: Vec<ChoiceOption>,
+    },
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Quest {
     pub id: String,
@@ -42,10 +105,58 @@ pub struct Quest {
     #[serde(default)]
     pub message: String,
 
+    /// Multi-beat cutscene. When non-empty, this replaces `message` as
+    /// what plays on completion; see `CutsceneBeat`.
+    #[serde(default)]
+    pub scene: Vec<CutsceneBeat>,
+
     pub conditions: Vec<Condition>,
     pub next_quest_id: String,
 }
 
+impl Quest {
+    /// Evaluates every condition against the player's input, executing
+    /// `user_command` for real (once) if any condition needs its output.
+    ///
+    /// Only call this with the player's original, unmangled input line --
+    /// i.e. from `supershell play`'s REPL. The shell hook's `_g` wrapper
+    /// already ran the command once itself and hands us "$cmd $*"
+    /// reconstructed from already-split shell words (quoting lost, e.g.
+    /// `grep "a b" f` becomes three tokens instead of two), so re-running
+    /// that reconstruction here would both execute it a second time and
+    /// validate a different command than the one the player actually ran.
+    /// The `--check` CLI path `_g` drives uses `check_without_exec` instead.
+    pub fn check(&self, user_command: &str) -> bool {
+        let exec_result = if self.conditions.iter().any(Condition::needs_exec) {
+            exec::run(user_command, Duration::from_millis(exec::DEFAULT_TIMEOUT_MS))
+        } else {
+            None
+        };
+
+        self.conditions
+            .iter()
+            .all(|c| c.is_met(user_command, exec_result.as_ref()))
+    }
+
+    /// Like `check`, but for callers that can't safely provide a real
+    /// execution of `user_command` -- the shell hook's `_g`, which has
+    /// already run the command once with different quoting than what we'd
+    /// reconstruct from it. Any condition that needs real command output is
+    /// treated as unmet rather than re-executing a reconstructed command.
+    pub fn check_without_exec(&self, user_command: &str) -> bool {
+        if self.conditions.iter().any(Condition::needs_exec) {
+            warn!(
+                "Quest '{}' has a condition that needs real command output; \
+                 refusing to re-run it via --check. Play it through `supershell play` instead.",
+                self.id
+            );
+            return false;
+        }
+
+        self.conditions.iter().all(|c| c.is_met(user_command, None))
+    }
+}
+
 pub fn load_quests(path: &str) -> HashMap<String, Quest> {
     let mut db = HashMap::new();
 
@@ -1,10 +1,90 @@
 use directories::UserDirs;
+use std::env;
+use std::fs;
 use std::io::Write;
+use std::path::{Path, PathBuf};
 use std::process::Command;
-use tempfile::Builder;
+use tempfile::{Builder, NamedTempFile, TempDir};
+
+/// Which shell the player's system is actually running.
+///
+/// We only need to know enough about each shell to build its init file and
+/// launch it with that init file instead of the user's real rc/config, so
+/// this stays a closed set rather than a generic "shell profile" trait.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Shell {
+    Bash,
+    Zsh,
+    Fish,
+}
+
+impl Shell {
+    /// Detects the user's shell from `$SHELL`, falling back to Bash.
+    pub fn detect() -> Self {
+        match env::var("SHELL") {
+            Ok(path) if path.ends_with("zsh") => Shell::Zsh,
+            Ok(path) if path.ends_with("fish") => Shell::Fish,
+            _ => Shell::Bash,
+        }
+    }
+
+    /// The binary name used to spawn this shell.
+    fn command_name(&self) -> &'static str {
+        match self {
+            Shell::Bash => "bash",
+            Shell::Zsh => "zsh",
+            Shell::Fish => "fish",
+        }
+    }
+
+    /// The extension our temp init file should carry, mostly for readability
+    /// when debugging a leftover temp file.
+    fn rc_suffix(&self) -> &'static str {
+        match self {
+            Shell::Bash => ".bash",
+            Shell::Zsh => ".zsh",
+            Shell::Fish => ".fish",
+        }
+    }
+
+    /// Builds the init file content for this shell, with `__BINARY_PATH__`
+    /// already substituted for the current executable.
+    fn render_rc(&self, binary_path: &str) -> String {
+        let template = match self {
+            Shell::Bash => BASH_RC_TEMPLATE,
+            Shell::Zsh => ZSH_RC_TEMPLATE,
+            Shell::Fish => FISH_RC_TEMPLATE,
+        };
+        template.replace("__BINARY_PATH__", binary_path)
+    }
+
+    /// Configures `cmd` to load `rc_path` as this shell's only startup file,
+    /// ignoring the user's real rc/config so the game's hooks are the only
+    /// ones in effect.
+    fn apply_rc(&self, cmd: &mut Command, rc_path: &std::path::Path) {
+        match self {
+            Shell::Bash => {
+                cmd.arg("--noprofile").arg("--rcfile").arg(rc_path);
+            }
+            Shell::Zsh => {
+                // Zsh has no --rcfile flag; it sources $ZDOTDIR/.zshrc instead,
+                // so we point ZDOTDIR at our temp file's parent directory and
+                // make sure our file is actually named ".zshrc" there.
+                cmd.env("ZDOTDIR", rc_path.parent().expect("temp file has a parent"));
+            }
+            Shell::Fish => {
+                cmd.arg("--no-config").arg("--init-command").arg(format!(
+                    "source {}",
+                    rc_path.to_string_lossy()
+                ));
+            }
+        }
+    }
+}
 
 // --- THE CYBERPUNK CONFIGURATION ---
-const SHELL_RC_TEMPLATE: &str = r#"
+
+const BASH_RC_TEMPLATE: &str = r#"
 # No-Op for standard input to prevent 'stdin: is not a tty' errors
 if [ -z "$PS1" ]; then
    return
@@ -81,6 +161,177 @@ echo -e "\e[0;90m   (Type 'exit' to disconnect)\e[0m\n"
 "__BINARY_PATH__" --refresh
 "#;
 
+const ZSH_RC_TEMPLATE: &str = r#"
+# 1. ACCESSIBLE CYBERPUNK PROMPT
+# Format: [ USER :: CONSTRUCT ] ~/current/path $
+PROMPT="%B%F{white}[ %F{cyan}user%F{white}::%F{cyan}construct%F{white} ] %F{magenta}%~%F{white} $ %f%b"
+
+# 2. PATH SETUP
+export PATH=$PATH:/bin:/usr/bin:/usr/local/bin
+
+# 3. THE GUARD (INTERCEPTOR)
+# Usage: _g <command> <args>
+# This function asks Rust for permission before running the command.
+function _g() {
+    local cmd=$1
+    shift
+
+    # A. Run the User's Command FIRST
+    command "$cmd" "$@"
+
+    # B. Run the Game Check (Directly to Terminal)
+    "__BINARY_PATH__" --check "$cmd $*"
+
+    # C. Check the Signal
+    if [ $? -eq 2 ]; then
+        clear
+        "__BINARY_PATH__" --refresh
+    fi
+}
+
+# 4. GAME ALIASES
+alias status='"__BINARY_PATH__" --status'
+alias menu='"__BINARY_PATH__" --menu'
+alias supershell='"__BINARY_PATH__"'
+function help() {
+    echo "\n%BSYSTEM COMMANDS%b"
+    echo "  status      - Display current objective."
+    echo "  menu        - Return to module selection."
+    echo "  exit        - Disconnect from the Construct."
+    echo ""
+}
+
+# 5. THE INFECTION (PUZZLE HOOKS)
+alias ls='_g ls'
+alias cd='_g cd'
+alias cat='_g cat'
+alias grep='_g grep'
+alias ssh='_g ssh'
+alias nano='_g nano'
+alias vim='_g vim'
+
+# 6. STARTUP SEQUENCE
+clear
+echo "\n>> NEURAL LINK ESTABLISHED."
+echo ">> WELCOME TO THE CONSTRUCT."
+echo "   (Type 'exit' to disconnect)\n"
+
+# Trigger the initial game state check
+"__BINARY_PATH__" --refresh
+"#;
+
+const FISH_RC_TEMPLATE: &str = r#"
+# 1. ACCESSIBLE CYBERPUNK PROMPT
+function fish_prompt
+    set_color --bold white
+    echo -n "[ "
+    set_color cyan
+    echo -n "user"
+    set_color --bold white
+    echo -n "::"
+    set_color cyan
+    echo -n "construct"
+    set_color --bold white
+    echo -n " ] "
+    set_color magenta
+    echo -n (prompt_pwd)
+    set_color --bold white
+    echo -n " \$ "
+    set_color normal
+end
+
+# 2. PATH SETUP
+set -gx PATH $PATH /bin /usr/bin /usr/local/bin
+
+# 3. THE GUARD (INTERCEPTOR)
+# Usage: _g <command> <args>
+# This function asks Rust for permission before running the command.
+function _g
+    set cmd $argv[1]
+    set -e argv[1]
+
+    # A. Run the User's Command FIRST
+    command $cmd $argv
+
+    # B. Run the Game Check (Directly to Terminal)
+    "__BINARY_PATH__" --check "$cmd $argv"
+
+    # C. Check the Signal
+    if test $status -eq 2
+        clear
+        "__BINARY_PATH__" --refresh
+    end
+end
+
+# 4. GAME ALIASES
+function status
+    "__BINARY_PATH__" --status
+end
+function menu
+    "__BINARY_PATH__" --menu
+end
+function supershell
+    "__BINARY_PATH__" $argv
+end
+function help
+    echo ""
+    echo "  :: SYSTEM COMMANDS ::"
+    echo "  status      - Display current objective."
+    echo "  menu        - Return to module selection."
+    echo "  exit        - Disconnect from the Construct."
+    echo ""
+end
+
+# 5. THE INFECTION (PUZZLE HOOKS)
+alias ls='_g ls'
+alias cd='_g cd'
+alias cat='_g cat'
+alias grep='_g grep'
+alias ssh='_g ssh'
+alias nano='_g nano'
+alias vim='_g vim'
+
+# 6. STARTUP SEQUENCE
+clear
+echo ""
+echo ">> NEURAL LINK ESTABLISHED."
+echo ">> WELCOME TO THE CONSTRUCT."
+echo "   (Type 'exit' to disconnect)"
+echo ""
+
+# Trigger the initial game state check
+"__BINARY_PATH__" --refresh
+"#;
+
+/// Where we write a shell's rendered init file before launching it.
+///
+/// Every other shell just gets a uniquely-named temp file. Zsh insists on
+/// sourcing a file literally named ".zshrc", so a fixed name is the only
+/// option there -- `Zsh` instead gets its own randomly-named temp
+/// *directory* (so the fixed filename inside it can never collide with a
+/// concurrent launch) and keeps that directory alive for as long as the rc
+/// file needs to exist.
+enum RcFile {
+    Temp(NamedTempFile),
+    ZshDir { _dir: TempDir, path: PathBuf },
+}
+
+impl RcFile {
+    fn path(&self) -> &Path {
+        match self {
+            RcFile::Temp(file) => file.path(),
+            RcFile::ZshDir { path, .. } => path,
+        }
+    }
+
+    fn write(&mut self, content: &str) -> std::io::Result<()> {
+        match self {
+            RcFile::Temp(file) => write!(file, "{}", content),
+            RcFile::ZshDir { path, .. } => fs::write(path, content),
+        }
+    }
+}
+
 pub fn launch_infected_session() {
     // 1. Check for nesting
     if std::env::var("CONSTRUCT_UPLINK").is_ok() {
@@ -106,31 +357,42 @@ pub fn launch_infected_session() {
         std::fs::create_dir_all(&construct_path).expect("Failed to create Construct dir");
     }
 
-    // 4. Inject path into the template
-    let rc_content = SHELL_RC_TEMPLATE.replace("__BINARY_PATH__", &current_exe);
+    // 4. Detect which shell we're infecting and render its init file
+    let shell = Shell::detect();
+    let rc_content = shell.render_rc(&current_exe);
 
     // 5. Create a temporary RC file
-    let mut temp_rc = Builder::new()
-        .prefix("construct_rc_")
-        .suffix(".bash")
-        .rand_bytes(5)
-        .tempfile()
-        .expect("Failed to create temp RC file");
+    let mut temp_rc = if shell == Shell::Zsh {
+        let dir = Builder::new()
+            .prefix("construct_zsh_")
+            .tempdir()
+            .expect("Failed to create temp ZDOTDIR");
+        let path = dir.path().join(".zshrc");
+        RcFile::ZshDir { _dir: dir, path }
+    } else {
+        RcFile::Temp(
+            Builder::new()
+                .prefix("construct_rc_")
+                .suffix(shell.rc_suffix())
+                .rand_bytes(5)
+                .tempfile()
+                .expect("Failed to create temp RC file"),
+        )
+    };
 
-    write!(temp_rc, "{}", rc_content).expect("Failed to write RC file");
+    temp_rc.write(&rc_content).expect("Failed to write RC file");
 
     // 6. Spawn the Shell
-    // We use --noprofile to ensure a clean slate
-    // We use --rcfile to force our custom config
-    // We use .current_dir() to force them into the game world
-    let status = Command::new("bash")
+    // We use .current_dir() to force them into the game world, and let the
+    // per-shell `apply_rc` wire up whatever mechanism that shell uses to load
+    // a substitute init file instead of the user's real one.
+    let mut command = Command::new(shell.command_name());
+    command
         .current_dir(&construct_path)
-        .env("CONSTRUCT_UPLINK", "1")
-        .arg("--noprofile")
-        .arg("--rcfile")
-        .arg(temp_rc.path())
-        .status()
-        .expect("Failed to launch shell");
+        .env("CONSTRUCT_UPLINK", "1");
+    shell.apply_rc(&mut command, temp_rc.path());
+
+    let status = command.status().expect("Failed to launch shell");
 
     // 7. Cleanup Message
     if status.success() {
@@ -1,5 +1,5 @@
 use serde::{Deserialize, Serialize};
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::fs;
 use std::path::Path;
 
@@ -10,6 +10,13 @@ pub struct GameState {
 
     // The "Journal": A list of everything the user has finished.
     pub completed_quests: HashSet<String>,
+
+    // Which quest each completed quest led to, keyed by quest id; for a
+    // quest with a `Choice` beat, that is the branch the player picked.
+    // Lets branching cutscenes be replayed/inspected later
+    // instead of only living as an in-memory `next_quest_id`.
+    #[serde(default)]
+    pub quest_choices: HashMap<String, String>,
 }
 
 impl GameState {
@@ -17,6 +24,7 @@ impl GameState {
         Self {
             current_quest_id: "00_init".to_string(),
             completed_quests: HashSet::new(),
+            quest_choices: HashMap::new(),
         }
     }
 
@@ -40,4 +48,13 @@ impl GameState {
         self.completed_quests.insert(self.current_quest_id.clone());
         self.current_quest_id = next_quest_id.to_string();
     }
+
+    /// Like `complete_current_quest`, but also records which branch
+    /// `quest_id` resolved to -- relevant when its cutscene contains a
+    /// `Choice` beat that can send the player down different paths.
+    pub fn complete_current_quest_with_choice(&mut self, quest_id: &str, next_quest_id: &str) {
+        self.quest_choices
+            .insert(quest_id.to_string(), next_quest_id.to_string());
+        self.complete_current_quest(next_quest_id);
+    }
 }
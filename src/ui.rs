@@ -1,11 +1,18 @@
+use crate::history::History;
+use crate::quest::{ChoiceOption, CutsceneBeat, Quest};
+use crate::state::GameState;
 use crossterm::{
     cursor::{Hide, MoveTo, MoveToColumn, MoveToNextLine, Show},
-    event::{Event, KeyCode, KeyEventKind, poll, read},
+    event::{Event, KeyCode, KeyEventKind, KeyModifiers, poll, read},
     execute,
     style::{Attribute, Color, Print, ResetColor, SetAttribute, SetForegroundColor},
     terminal::{Clear, ClearType, disable_raw_mode, enable_raw_mode, size},
 };
+use signal_hook::consts::{SIGINT, SIGQUIT, SIGTERM};
+use signal_hook::iterator::Signals;
+use std::collections::HashMap;
 use std::io::{Write, stdout};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::thread;
 use std::time::Duration;
 use textwrap::fill;
@@ -18,36 +25,84 @@ struct TerminalGuard;
 
 impl TerminalGuard {
     fn new() -> Self {
-        enable_raw_mode().expect("Failed to enable raw mode");
+        enter_raw_mode();
         let mut stdout = stdout();
         execute!(stdout, Hide, MoveTo(0, 0)).unwrap(); // Hide cursor
         Self
     }
 }
 
+/// Enables raw mode and re-arms `TERMINAL_RESTORED`. Every raw-mode session
+/// -- a `TerminalGuard` cutscene or `read_command_line`'s own prompt -- must
+/// go through this rather than calling `enable_raw_mode()` directly, or a
+/// signal landing during a *later* session would find the latch already
+/// tripped from an earlier one and silently skip `restore_terminal`.
+fn enter_raw_mode() {
+    enable_raw_mode().expect("Failed to enable raw mode");
+    TERMINAL_RESTORED.store(false, Ordering::SeqCst);
+}
+
 // This runs AUTOMATICALLY when the cutscene ends or crashes
 impl Drop for TerminalGuard {
     fn drop(&mut self) {
-        let mut stdout = stdout();
-        disable_raw_mode().unwrap_or(()); // Force raw mode off
-        execute!(
-            stdout,
-            Show, // Bring cursor back
-            ResetColor,
-            SetAttribute(Attribute::Reset),
-            Clear(ClearType::All), // Clean up our mess
-            MoveTo(0, 0)           // Reset position
-        )
-        .unwrap_or(());
+        restore_terminal();
     }
 }
 
+/// Guards so the cleanup sequence below only ever runs once, whether it's
+/// triggered by `TerminalGuard::drop` on a normal return or by the signal
+/// handler installed in `install_signal_handler` when the process is
+/// interrupted mid-cutscene.
+static TERMINAL_RESTORED: AtomicBool = AtomicBool::new(false);
+
+fn restore_terminal() {
+    if TERMINAL_RESTORED.swap(true, Ordering::SeqCst) {
+        return;
+    }
+    let mut stdout = stdout();
+    disable_raw_mode().unwrap_or(()); // Force raw mode off
+    execute!(
+        stdout,
+        Show, // Bring cursor back
+        ResetColor,
+        SetAttribute(Attribute::Reset),
+        Clear(ClearType::All), // Clean up our mess
+        MoveTo(0, 0)           // Reset position
+    )
+    .unwrap_or(());
+}
+
+/// Installs a background signal handler so Ctrl-C (or a `kill`/`SIGQUIT`)
+/// during `play_cutscene` restores the terminal instead of leaving the
+/// shell stuck in raw mode with a hidden cursor. Call once at startup.
+pub fn install_signal_handler() {
+    let mut signals = match Signals::new([SIGINT, SIGTERM, SIGQUIT]) {
+        Ok(signals) => signals,
+        Err(_) => return,
+    };
+    thread::spawn(move || {
+        if signals.forever().next().is_some() {
+            restore_terminal();
+            std::process::exit(130);
+        }
+    });
+}
+
 // --- THE RENDERER ---
 pub fn play_cutscene(text: &str) {
     // A. Initialize the Guard (Enables Raw Mode immediately)
     let _guard = TerminalGuard::new();
     let mut stdout = stdout();
 
+    type_out(&mut stdout, text);
+    wait_for_continue(&mut stdout);
+}
+
+/// Types out `text` with a per-character typewriter effect. Pressing Space
+/// jumps straight to the fully-printed text, word-wrapped to the terminal
+/// width; the typewriter pass itself prints `text` without re-wrapping it.
+/// Shared by `play_cutscene` and the `Text` beats in `play_quest_scene`.
+fn type_out(stdout: &mut std::io::Stdout, text: &str) {
     // WRAPPING
     let (cols, _) = size().unwrap_or((80, 24));
 
@@ -115,8 +170,11 @@ pub fn play_cutscene(text: &str) {
         print!("{}", aligned_block);
         stdout.flush().unwrap();
     }
+}
 
-    // PROMPT
+/// Prints the "press space to continue" footer and blocks until Space is
+/// pressed.
+fn wait_for_continue(stdout: &mut std::io::Stdout) {
     execute!(
         stdout,
         Print("\r\n\r\n"),
@@ -125,7 +183,6 @@ pub fn play_cutscene(text: &str) {
     )
     .unwrap();
 
-    // Loop until Space is pressed
     loop {
         if let Event::Key(key) = read().unwrap() {
             if key.kind == KeyEventKind::Press && key.code == KeyCode::Char(' ') {
@@ -134,3 +191,303 @@ pub fn play_cutscene(text: &str) {
         }
     }
 }
+
+/// Sleeps for `duration`, bailing out early if Space is pressed -- the same
+/// skip behavior as the typewriter effect, for `Pause` beats.
+fn skippable_sleep(duration: Duration) {
+    let step = Duration::from_millis(25);
+    let mut remaining = duration;
+
+    while remaining > Duration::ZERO {
+        if poll(Duration::from_secs(0)).unwrap_or(false) {
+            if let Event::Key(key) = read().unwrap() {
+                if key.kind == KeyEventKind::Press && key.code == KeyCode::Char(' ') {
+                    break;
+                }
+            }
+        }
+
+        let sleep_for = step.min(remaining);
+        thread::sleep(sleep_for);
+        remaining -= sleep_for;
+    }
+}
+
+/// Pads `text` with spaces so it's centered within `width` columns.
+fn center(text: &str, width: usize) -> String {
+    let len = text.chars().count();
+    if len >= width {
+        return text.to_string();
+    }
+    let left = (width - len) / 2;
+    let right = width - len - left;
+    format!("{}{}{}", " ".repeat(left), text, " ".repeat(right))
+}
+
+/// Renders `options` as centered, bordered boxes with the current
+/// selection highlighted, and lets the player move between them with the
+/// arrow keys. Returns the chosen option's `next_quest_id`, or `None` if
+/// there are no options to choose from.
+fn run_choice_prompt(
+    stdout: &mut std::io::Stdout,
+    prompt: &str,
+    options: &[ChoiceOption],
+) -> Option<String> {
+    if options.is_empty() {
+        return None;
+    }
+
+    let (cols, _) = size().unwrap_or((80, 24));
+    let width = cols as usize;
+    let mut selected = 0usize;
+
+    loop {
+        execute!(stdout, Clear(ClearType::All), MoveTo(0, 0)).unwrap();
+        execute!(
+            stdout,
+            SetForegroundColor(Color::Green),
+            SetAttribute(Attribute::Bold),
+            Print(format!("{}\r\n\r\n", center(prompt, width))),
+            SetAttribute(Attribute::Reset),
+            ResetColor,
+        )
+        .unwrap();
+
+        for (i, option) in options.iter().enumerate() {
+            let box_width = option.label.chars().count() + 4;
+            let top = format!("┌{}┐", "─".repeat(box_width - 2));
+            let middle = format!("│ {} │", option.label);
+            let bottom = format!("└{}┘", "─".repeat(box_width - 2));
+
+            if i == selected {
+                execute!(
+                    stdout,
+                    SetForegroundColor(Color::Black),
+                    SetAttribute(Attribute::Reverse)
+                )
+                .unwrap();
+            } else {
+                execute!(stdout, SetForegroundColor(Color::DarkGrey)).unwrap();
+            }
+
+            execute!(
+                stdout,
+                Print(format!("{}\r\n", center(&top, width))),
+                Print(format!("{}\r\n", center(&middle, width))),
+                Print(format!("{}\r\n\r\n", center(&bottom, width))),
+                SetAttribute(Attribute::Reset),
+                ResetColor,
+            )
+            .unwrap();
+        }
+        stdout.flush().unwrap();
+
+        let Ok(Event::Key(key)) = read() else {
+            continue;
+        };
+        if key.kind != KeyEventKind::Press {
+            continue;
+        }
+
+        match key.code {
+            KeyCode::Up | KeyCode::Left => {
+                selected = selected.checked_sub(1).unwrap_or(options.len() - 1);
+            }
+            KeyCode::Down | KeyCode::Right => {
+                selected = (selected + 1) % options.len();
+            }
+            KeyCode::Enter => return options.get(selected).map(|o| o.next_quest_id.clone()),
+            _ => {}
+        }
+    }
+}
+
+/// Plays a quest's cutscene and returns which quest to advance to.
+///
+/// Quests with no `scene` keep the old behavior: type out `message`, wait
+/// for Space, then follow `next_quest_id`. Quests with a `scene` play each
+/// beat in order; if any `Choice` beat is resolved, its option's
+/// `next_quest_id` overrides the quest's own once the scene ends.
+pub fn play_quest_scene(quest: &Quest) -> String {
+    if quest.scene.is_empty() {
+        play_cutscene(&quest.message);
+        return quest.next_quest_id.clone();
+    }
+
+    let _guard = TerminalGuard::new();
+    let mut stdout = stdout();
+    let mut chosen_next: Option<String> = None;
+
+    for beat in &quest.scene {
+        match beat {
+            CutsceneBeat::Text { body } => {
+                type_out(&mut stdout, body);
+                wait_for_continue(&mut stdout);
+            }
+            CutsceneBeat::Pause { ms } => skippable_sleep(Duration::from_millis(*ms)),
+            CutsceneBeat::Choice { prompt, options } => {
+                if let Some(next) = run_choice_prompt(&mut stdout, prompt, options) {
+                    chosen_next = Some(next);
+                }
+            }
+        }
+    }
+
+    chosen_next.unwrap_or_else(|| quest.next_quest_id.clone())
+}
+
+// --- THE INTERACTIVE PLAY LOOP ---
+
+/// Runs `supershell play`: a self-contained REPL that keeps redrawing the
+/// current protocol and re-checking the player's input in-process, instead
+/// of relaunching the binary once per command the way the shell `_g` hook
+/// does.
+pub fn run_play_loop(
+    quest_db: &HashMap<String, Quest>,
+    mut game: GameState,
+    save_file: &str,
+    history: &History,
+) {
+    let mut command_log: Vec<String> = history
+        .load_all()
+        .into_iter()
+        .map(|entry| entry.command)
+        .collect();
+
+    loop {
+        draw_quest_header(&game, quest_db);
+
+        let Some(line) = read_command_line(&command_log) else {
+            println!("\n>> [SYSTEM] Session ended.\n");
+            break;
+        };
+
+        let input = line.trim();
+        if input == "exit" || input == "quit" {
+            println!(">> [SYSTEM] Session ended.\n");
+            break;
+        }
+
+        let Some(quest) = quest_db.get(&game.current_quest_id) else {
+            println!("STATUS: No active protocols. System idle.\n");
+            continue;
+        };
+
+        let all_met = quest.check(input);
+        history.record(&game.current_quest_id, input, all_met);
+        command_log.push(input.to_string());
+
+        if all_met {
+            let next_quest_id = play_quest_scene(quest);
+            game.complete_current_quest_with_choice(&quest.id, &next_quest_id);
+            game.save(save_file);
+        } else {
+            println!("[!] Criteria not met. Try again.\n");
+        }
+    }
+}
+
+/// Draws the current protocol name/task at the top of a fresh screen.
+fn draw_quest_header(game: &GameState, quest_db: &HashMap<String, Quest>) {
+    let mut stdout = stdout();
+    execute!(stdout, Clear(ClearType::All), MoveTo(0, 0)).unwrap();
+
+    match quest_db.get(&game.current_quest_id) {
+        Some(quest) => {
+            execute!(
+                stdout,
+                SetForegroundColor(Color::Green),
+                SetAttribute(Attribute::Bold),
+                Print(format!("[PROTOCOL] {}\r\n", quest.name)),
+                SetAttribute(Attribute::Reset),
+                ResetColor,
+            )
+            .unwrap();
+        }
+        None => {
+            execute!(stdout, Print("STATUS: No active protocols.\r\n")).unwrap();
+        }
+    }
+    stdout.flush().unwrap();
+}
+
+/// Reads one full command line in raw mode, with basic backspace editing
+/// and Up/Down recall through `history` (oldest first, most recent last).
+/// Returns `None` on Ctrl-C/Ctrl-D so the caller can exit the loop cleanly.
+fn read_command_line(history: &[String]) -> Option<String> {
+    enter_raw_mode();
+    let mut stdout = stdout();
+    execute!(stdout, Print("> ")).unwrap();
+    stdout.flush().unwrap();
+
+    let mut buffer = String::new();
+    // One past the last history entry represents "not recalling anything,
+    // just the in-progress line".
+    let mut history_index = history.len();
+    let mut draft = String::new();
+
+    let redraw = |stdout: &mut std::io::Stdout, buffer: &str| {
+        execute!(
+            stdout,
+            MoveToColumn(0),
+            Clear(ClearType::CurrentLine),
+            Print(format!("> {}", buffer))
+        )
+        .unwrap();
+        stdout.flush().unwrap();
+    };
+
+    let result = loop {
+        let Ok(Event::Key(key)) = read() else {
+            continue;
+        };
+        if key.kind != KeyEventKind::Press {
+            continue;
+        }
+
+        if key.modifiers.contains(KeyModifiers::CONTROL)
+            && matches!(key.code, KeyCode::Char('c') | KeyCode::Char('d'))
+        {
+            break None;
+        }
+
+        match key.code {
+            KeyCode::Enter => break Some(buffer.clone()),
+            KeyCode::Backspace => {
+                if buffer.pop().is_some() {
+                    redraw(&mut stdout, &buffer);
+                }
+            }
+            KeyCode::Up => {
+                if history_index > 0 {
+                    if history_index == history.len() {
+                        draft = buffer.clone();
+                    }
+                    history_index -= 1;
+                    buffer = history[history_index].clone();
+                    redraw(&mut stdout, &buffer);
+                }
+            }
+            KeyCode::Down => {
+                if history_index < history.len() {
+                    history_index += 1;
+                    buffer = history
+                        .get(history_index)
+                        .cloned()
+                        .unwrap_or_else(|| draft.clone());
+                    redraw(&mut stdout, &buffer);
+                }
+            }
+            KeyCode::Char(c) => {
+                buffer.push(c);
+                execute!(stdout, Print(c)).unwrap();
+                stdout.flush().unwrap();
+            }
+            _ => {}
+        }
+    };
+
+    execute!(stdout, Print("\r\n")).unwrap();
+    disable_raw_mode().ok();
+    result
+}
@@ -1,17 +1,71 @@
 // world.rs
 
 use crate::actions::SetupAction;
+use crate::state::GameState;
 use directories::UserDirs;
-use log::{debug, error, info};
+use log::{debug, error, info, warn};
+use rayon::prelude::*;
+use regex::Regex;
+use std::collections::hash_map::DefaultHasher;
+use std::env;
 use std::fs;
+use std::hash::{Hash, Hasher};
 use std::io::Write;
-use std::path::PathBuf;
+use std::path::{Component, Path, PathBuf};
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::time::SystemTime;
+
+/// The result of running a single [`SetupAction`].
+#[derive(Debug)]
+pub enum ActionOutcome {
+    Success,
+    /// The action was refused before it ran (e.g. `safe_path` rejected it).
+    Skipped(String),
+    Failed(String),
+}
+
+/// Per-action outcome from a [`WorldEngine::build_scenario`] run, so the
+/// caller gets a real signal instead of a half-built world that silently
+/// looks successful.
+#[derive(Debug)]
+pub struct ActionStatus {
+    pub action: SetupAction,
+    pub outcome: ActionOutcome,
+}
+
+/// Summary of an entire scenario build.
+#[derive(Debug, Default)]
+pub struct ScenarioReport {
+    pub statuses: Vec<ActionStatus>,
+}
+
+impl ScenarioReport {
+    pub fn all_succeeded(&self) -> bool {
+        self.statuses
+            .iter()
+            .all(|s| matches!(s.outcome, ActionOutcome::Success))
+    }
+
+    pub fn failures(&self) -> impl Iterator<Item = &ActionStatus> {
+        self.statuses
+            .iter()
+            .filter(|s| !matches!(s.outcome, ActionOutcome::Success))
+    }
+}
 
 pub struct WorldEngine {
     root_path: PathBuf,
 }
 
 impl WorldEngine {
+    /// Builds a `WorldEngine` rooted anywhere, bypassing the real `~/Construct`
+    /// lookup in [`WorldEngine::new`] so tests can exercise `safe_path`
+    /// against a disposable temp directory instead of the player's actual home.
+    #[cfg(test)]
+    fn with_root(root_path: PathBuf) -> Self {
+        WorldEngine { root_path }
+    }
+
     pub fn new() -> Self {
         // 1. Locate the User's Home Directory safely
         let user_dirs = UserDirs::new().expect("Critical: Could not find User Home.");
@@ -34,61 +88,388 @@ impl WorldEngine {
         }
     }
 
-    /// The Main Loop: Reads YAML instructions and executes them
-    pub fn build_scenario(&self, actions: &[SetupAction]) {
-        for action in actions {
-            match action {
-                SetupAction::CreateDir { path } => {
-                    let target = self.safe_path(path);
-                    debug!("Action [CreateDir]: {:?}", target);
-                    if let Err(e) = fs::create_dir_all(target) {
-                        error!("Action Failed [CreateDir]: {}", e);
+    /// Reads YAML instructions and executes them, returning a per-action
+    /// report rather than swallowing every failure into a log line.
+    ///
+    /// `CreateDir`/`CreateFile` are safe to run in any order (each
+    /// `CreateFile` creates its own parent directory), so consecutive runs
+    /// of them are built in parallel via rayon. `RemovePath`/`ResetWorld` can affect paths a
+    /// later action writes into (or a `ResetWorld` wiping everything an
+    /// earlier action just created), so they always run on their own,
+    /// in order, with any parallel batch around them fully drained first.
+    pub fn build_scenario(&self, actions: &[SetupAction], game: &GameState) -> ScenarioReport {
+        let total = actions.len();
+        let done = AtomicUsize::new(0);
+        let mut statuses = Vec::with_capacity(total);
+
+        let mut batch_start = 0;
+        while batch_start < actions.len() {
+            match &actions[batch_start] {
+                SetupAction::CreateDir { .. } | SetupAction::CreateFile { .. } => {
+                    let batch_end = actions[batch_start..]
+                        .iter()
+                        .position(|a| {
+                            !matches!(a, SetupAction::CreateDir { .. } | SetupAction::CreateFile { .. })
+                        })
+                        .map(|offset| batch_start + offset)
+                        .unwrap_or(actions.len());
+
+                    let batch = &actions[batch_start..batch_end];
+                    let mut batch_statuses: Vec<ActionStatus> = batch
+                        .par_iter()
+                        .map(|action| {
+                            let status = self.run_action(action, game);
+                            let progress = done.fetch_add(1, Ordering::Relaxed) + 1;
+                            report_progress(progress, total);
+                            status
+                        })
+                        .collect();
+                    statuses.append(&mut batch_statuses);
+
+                    batch_start = batch_end;
+                }
+                SetupAction::RemovePath { .. } | SetupAction::ResetWorld => {
+                    let status = self.run_action(&actions[batch_start], game);
+                    let progress = done.fetch_add(1, Ordering::Relaxed) + 1;
+                    report_progress(progress, total);
+                    statuses.push(status);
+
+                    batch_start += 1;
+                }
+            }
+        }
+
+        let report = ScenarioReport { statuses };
+        if !report.all_succeeded() {
+            let failed = report.failures().count();
+            error!(
+                "Scenario build finished with {}/{} action(s) not applied cleanly",
+                failed, total
+            );
+        }
+        report
+    }
+
+    /// Runs a single action and turns its result into an [`ActionStatus`].
+    fn run_action(&self, action: &SetupAction, game: &GameState) -> ActionStatus {
+        let outcome = match action {
+            SetupAction::CreateDir { path } => {
+                let path = resolve_env(path, game);
+                match self.safe_path(&path) {
+                    Some(target) => {
+                        debug!("Action [CreateDir]: {:?}", target);
+                        match fs::create_dir_all(target) {
+                            Ok(()) => ActionOutcome::Success,
+                            Err(e) => ActionOutcome::Failed(e.to_string()),
+                        }
                     }
+                    None => ActionOutcome::Skipped("unsafe path".into()),
                 }
-                SetupAction::CreateFile { path, content } => {
-                    let target = self.safe_path(path);
-                    debug!("Action [CreateFile]: {:?}", target);
-                    if let Err(e) =
-                        fs::File::create(&target).and_then(|mut f| f.write_all(content.as_bytes()))
-                    {
-                        error!("Action Failed [CreateFile]: {}", e);
+            }
+            SetupAction::CreateFile { path, content } => {
+                let path = resolve_env(path, game);
+                let content = resolve_env(content, game);
+                match self.safe_path(&path) {
+                    Some(target) => {
+                        debug!("Action [CreateFile]: {:?}", target);
+                        // `CreateDir` and `CreateFile` run concurrently within a
+                        // batch, so a `CreateFile` into a directory its own batch
+                        // is also creating can't rely on that `CreateDir` having
+                        // already run first. Ensure the parent ourselves instead
+                        // of depending on action order.
+                        let ensure_parent = match target.parent() {
+                            Some(parent) => fs::create_dir_all(parent),
+                            None => Ok(()),
+                        };
+                        match ensure_parent.and_then(|()| fs::File::create(&target)).and_then(
+                            |mut f| f.write_all(content.as_bytes()),
+                        ) {
+                            Ok(()) => ActionOutcome::Success,
+                            Err(e) => ActionOutcome::Failed(e.to_string()),
+                        }
                     }
+                    None => ActionOutcome::Skipped("unsafe path".into()),
                 }
-                SetupAction::RemovePath { path } => {
-                    let target = self.safe_path(path);
+            }
+            SetupAction::RemovePath { path } => match self.safe_path(path) {
+                Some(target) => {
                     debug!("Action [RemovePath]: {:?}", target);
-                    if target.exists() {
-                        if target.is_dir() {
-                            fs::remove_dir_all(target).ok();
-                        } else {
-                            fs::remove_file(target).ok();
-                        }
+                    let result = if !target.exists() {
+                        Ok(())
+                    } else if target.is_dir() {
+                        fs::remove_dir_all(&target)
+                    } else {
+                        fs::remove_file(&target)
+                    };
+                    match result {
+                        Ok(()) => ActionOutcome::Success,
+                        Err(e) => ActionOutcome::Failed(e.to_string()),
                     }
                 }
-                SetupAction::ResetWorld => {
-                    info!("Action [ResetWorld]: Purging Construct directory.");
-                    if self.root_path.ends_with("Construct") && self.root_path.exists() {
-                        if let Ok(entries) = fs::read_dir(&self.root_path) {
-                            for entry in entries.flatten() {
-                                let path = entry.path();
-                                if path.is_dir() {
-                                    fs::remove_dir_all(path).ok();
-                                } else {
-                                    fs::remove_file(path).ok();
-                                }
-                            }
+                None => ActionOutcome::Skipped("unsafe path".into()),
+            },
+            SetupAction::ResetWorld => {
+                info!("Action [ResetWorld]: Purging Construct directory.");
+                if self.root_path.ends_with("Construct") && self.root_path.exists() {
+                    let purge = fs::read_dir(&self.root_path).map(|entries| {
+                        for entry in entries.flatten() {
+                            let path = entry.path();
+                            let _ = if path.is_dir() {
+                                fs::remove_dir_all(path)
+                            } else {
+                                fs::remove_file(path)
+                            };
                         }
+                    });
+                    match purge {
+                        Ok(()) => ActionOutcome::Success,
+                        Err(e) => ActionOutcome::Failed(e.to_string()),
                     }
+                } else {
+                    ActionOutcome::Skipped("Construct root missing".into())
                 }
             }
+        };
+
+        if let ActionOutcome::Failed(reason) = &outcome {
+            error!("Action Failed [{:?}]: {}", action, reason);
+        }
+
+        ActionStatus {
+            action: action.clone(),
+            outcome,
         }
     }
 
-    /// SAFETY: Joins the user input to ~/Construct
-    /// Prevents users from writing "setup_action: ../../System32"
-    fn safe_path(&self, relative_path: &str) -> PathBuf {
-        // A real production app needs ".." sanitization here.
-        // For now, we trust the YAML writer (you).
-        self.root_path.join(relative_path)
+    /// SAFETY: Resolves `relative_path` against `~/Construct` while refusing
+    /// to let it escape that root.
+    ///
+    /// Quest YAML is community content, so we can't trust it the way we
+    /// trust our own code: a `CreateFile { path: "../../.bashrc" }` or a
+    /// pre-planted symlink pointing outside `~/Construct` must not be able
+    /// to touch anything on the player's real system. Returns `None`
+    /// (logging why) rather than panicking, so one bad action just gets
+    /// skipped instead of aborting the whole scenario.
+    fn safe_path(&self, relative_path: &str) -> Option<PathBuf> {
+        let relative = Path::new(relative_path);
+
+        // 1. Walk the components ourselves: reject absolute paths outright,
+        // and resolve "." / ".." by popping off our own accumulated stack
+        // rather than the filesystem's, so "../../etc" can never climb
+        // above the root no matter how many ".." segments it has.
+        let mut stack: Vec<Component> = Vec::new();
+        for component in relative.components() {
+            match component {
+                Component::Normal(_) => stack.push(component),
+                Component::CurDir => {}
+                Component::ParentDir => {
+                    if stack.pop().is_none() {
+                        warn_escape(relative_path, "path climbs above the Construct root");
+                        return None;
+                    }
+                }
+                Component::RootDir | Component::Prefix(_) => {
+                    warn_escape(relative_path, "absolute paths are not allowed");
+                    return None;
+                }
+            }
+        }
+
+        let joined = stack.iter().fold(self.root_path.clone(), |acc, c| acc.join(c));
+
+        // 2. Reject symlinked intermediate components. A quest could plant a
+        // symlink (e.g. "logs" -> "/etc") before asking us to write into
+        // "logs/whatever", which would otherwise tunnel straight out of the
+        // Construct root without ever writing a literal "..".
+        let mut checked = self.root_path.clone();
+        for component in joined
+            .strip_prefix(&self.root_path)
+            .ok()?
+            .components()
+        {
+            checked = checked.join(component);
+            if checked.is_symlink() {
+                warn_escape(relative_path, "path passes through a symlink");
+                return None;
+            }
+        }
+
+        // 3. Canonicalize whatever of the path already exists (the deepest
+        // existing ancestor) and confirm it's still rooted under the
+        // canonical Construct directory before we hand the path back.
+        let canonical_root = self.root_path.canonicalize().ok()?;
+        let mut probe = joined.clone();
+        let canonical_ancestor = loop {
+            if let Ok(canonical) = probe.canonicalize() {
+                break canonical;
+            }
+            if !probe.pop() {
+                warn_escape(relative_path, "could not resolve any existing ancestor");
+                return None;
+            }
+        };
+
+        if !canonical_ancestor.starts_with(&canonical_root) {
+            warn_escape(relative_path, "resolved path escapes the Construct root");
+            return None;
+        }
+
+        Some(joined)
+    }
+}
+
+fn warn_escape(path: &str, reason: &str) {
+    error!("Refusing setup action for {:?}: {}", path, reason);
+}
+
+/// Logs coarse progress for large scenarios instead of going silent until
+/// the whole batch is done. Every tenth of the way is plenty for a quest
+/// pack's setup_actions list; tiny scenarios just report once at the end.
+fn report_progress(done: usize, total: usize) {
+    if total == 0 {
+        return;
+    }
+    let step = total / 10;
+    if done == total || (step > 0 && done % step == 0) {
+        info!("Building scenario: {}/{} actions complete", done, total);
+    }
+}
+
+// --- TEMPLATE EXPANSION ---
+//
+// Lets quest authors write `${VAR}` tokens in a `CreateFile`/`CreateDir`
+// path or content so scenarios can be parameterized instead of shipping
+// the exact same, spoiler-able world to every player.
+
+/// Substitutes every `${TOKEN}` in `text` in a single pass: environment
+/// variables, the player's current quest id, and a couple of built-in
+/// generators (`${RANDOM_HEX}`, `${USER}`). Unknown tokens are left as
+/// literal text (with a warning logged) rather than failing the scenario.
+fn resolve_env(text: &str, game: &GameState) -> String {
+    let re = Regex::new(r"\$\{([A-Za-z_][A-Za-z0-9_]*)\}").unwrap();
+    re.replace_all(text, |caps: &regex::Captures| match resolve_token(&caps[1], game) {
+        Some(value) => value,
+        None => {
+            warn!("Unknown template token '${{{}}}' left unexpanded", &caps[1]);
+            caps[0].to_string()
+        }
+    })
+    .into_owned()
+}
+
+fn resolve_token(name: &str, game: &GameState) -> Option<String> {
+    match name {
+        "RANDOM_HEX" => Some(random_hex()),
+        "USER" => env::var("USER").or_else(|_| env::var("USERNAME")).ok(),
+        "QUEST_ID" => Some(game.current_quest_id.clone()),
+        _ => env::var(name).ok(),
+    }
+}
+
+/// A short pseudo-random hex string, unique enough per session to give each
+/// player their own secret without pulling in a `rand` dependency.
+fn random_hex() -> String {
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+    let seed = COUNTER.fetch_add(1, Ordering::Relaxed);
+
+    let mut hasher = DefaultHasher::new();
+    SystemTime::now().hash(&mut hasher);
+    std::process::id().hash(&mut hasher);
+    seed.hash(&mut hasher);
+
+    format!("{:016x}", hasher.finish())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn engine_in(root: &Path) -> WorldEngine {
+        fs::create_dir_all(root).unwrap();
+        WorldEngine::with_root(root.to_path_buf())
+    }
+
+    #[test]
+    fn safe_path_resolves_a_legitimate_nested_path() {
+        let root = tempfile::tempdir().unwrap();
+        let engine = engine_in(root.path());
+
+        let resolved = engine.safe_path("logs/today.txt").unwrap();
+
+        assert_eq!(resolved, root.path().join("logs").join("today.txt"));
+    }
+
+    #[test]
+    fn safe_path_rejects_a_parent_dir_escape() {
+        let root = tempfile::tempdir().unwrap();
+        let engine = engine_in(root.path());
+
+        assert!(engine.safe_path("../../etc/passwd").is_none());
+    }
+
+    #[test]
+    fn safe_path_rejects_an_absolute_path() {
+        let root = tempfile::tempdir().unwrap();
+        let engine = engine_in(root.path());
+
+        assert!(engine.safe_path("/etc/passwd").is_none());
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn safe_path_rejects_a_symlink_escape() {
+        let root = tempfile::tempdir().unwrap();
+        let outside = tempfile::tempdir().unwrap();
+        let engine = engine_in(root.path());
+
+        std::os::unix::fs::symlink(outside.path(), root.path().join("linked")).unwrap();
+
+        assert!(engine.safe_path("linked/whatever").is_none());
+    }
+
+    #[test]
+    fn resolve_env_expands_known_tokens() {
+        let game = GameState::new();
+
+        let expanded = resolve_env("quest is ${QUEST_ID}", &game);
+
+        assert_eq!(expanded, format!("quest is {}", game.current_quest_id));
+    }
+
+    #[test]
+    fn resolve_env_leaves_unknown_tokens_untouched() {
+        let game = GameState::new();
+
+        let expanded = resolve_env("${DEFINITELY_NOT_A_REAL_TOKEN}", &game);
+
+        assert_eq!(expanded, "${DEFINITELY_NOT_A_REAL_TOKEN}");
+    }
+
+    /// Regression test for a batch where a `CreateFile` lands inside a
+    /// directory its own batch is also creating: `CreateDir`/`CreateFile`
+    /// run concurrently within a batch, so without `CreateFile` ensuring its
+    /// own parent this raced on whether the `CreateDir` thread had already
+    /// run, intermittently failing with ENOENT.
+    #[test]
+    fn build_scenario_creates_nested_file_alongside_its_parent_dir() {
+        let root = tempfile::tempdir().unwrap();
+        let engine = engine_in(root.path());
+        let game = GameState::new();
+
+        let actions = vec![
+            SetupAction::CreateDir {
+                path: "logs".into(),
+            },
+            SetupAction::CreateFile {
+                path: "logs/today.txt".into(),
+                content: "hello".into(),
+            },
+        ];
+
+        let report = engine.build_scenario(&actions, &game);
+
+        assert!(report.all_succeeded(), "{:?}", report.statuses);
+        let content = fs::read_to_string(root.path().join("logs").join("today.txt")).unwrap();
+        assert_eq!(content, "hello");
     }
 }